@@ -1,13 +1,53 @@
 use std::error::Error;
 use std::io;
 use std::io::Write;
+use std::str::FromStr;
 use base64::Engine;
 use base64::engine::general_purpose;
+use bitcoin::{Address, Amount, Psbt, Transaction};
+use bitcoin::hex::DisplayHex;
 use bitcoind::bitcoincore_rpc::{Client, RpcApi};
-use crate::transaction::{create_dust_psbt, get_dust_utxos};
+use bitcoind::bitcoincore_rpc::bitcoincore_rpc_json::ListUnspentResultEntry;
+use crate::transaction::{create_dust_psbt, estimate_sweep_vbytes, filter_protected_utxos, get_dust_utxos, select_economic_utxos, verify_finalized_transaction};
 
+mod ord;
 mod transaction;
 
+/// Fetches the live minimum relay/mempool fee from the connected node and
+/// converts it from BTC/kvB (as returned by `getmempoolinfo`) to sat/vB.
+///
+/// The conversion floors the result at `1`, since a fee rate of zero sat/vB
+/// is not meaningful for dust classification.
+fn query_min_relay_fee(client: &Client) -> Result<u64, Box<dyn Error>> {
+    let mempool_info = client.get_mempool_info()?;
+    let sat_per_vb = (mempool_info.mempool_min_fee.to_btc() * 100_000_000.0 / 1000.0).round() as u64;
+    Ok(sat_per_vb.max(1))
+}
+
+/// Parameters selecting which dust UTXOs to operate on, shared by [`DustBuster::create_psbt`]
+/// and [`DustBuster::sweep`].
+///
+/// # Fields
+///
+/// * `min_relay_fee` - The minimum relay fee rate (in sat/vB) used to classify dust UTXOs.
+///   If `None`, the live minimum mempool fee is fetched from the connected node.
+/// * `address` - The address whose dust UTXOs should be selected.
+/// * `utxo_count` - The maximum number of UTXOs to include in the transaction.
+/// * `ord_url` - Base URL of an ord server used to exclude inscription/rune-bearing UTXOs.
+///   If `None`, no such check is performed.
+/// * `force` - If `true`, an outpoint that could not be checked against `ord_url` is
+///   included anyway instead of aborting.
+/// * `destination` - If set, consolidate the dust into a single output at this address
+///   instead of burning it to an OP_RETURN output.
+pub struct DustSelection {
+    pub min_relay_fee: Option<u64>,
+    pub address: String,
+    pub utxo_count: u64,
+    pub ord_url: Option<String>,
+    pub force: bool,
+    pub destination: Option<String>,
+}
+
 /// A utility for managing and consolidating dust UTXOs in a Bitcoin wallet.
 ///
 /// `DustBuster` provides functions to list, filter, and create transactions
@@ -44,6 +84,7 @@ impl DustBuster {
     /// # Arguments
     ///
     /// * `min_relay_fee` - The minimum relay fee rate (in sat/vB) to classify dust UTXOs.
+    ///   If `None`, the live minimum mempool fee is fetched from the connected node.
     /// * `address` - An optional Bitcoin address to filter UTXOs by. If `None`, all UTXOs are considered.
     ///
     /// # Returns
@@ -57,8 +98,12 @@ impl DustBuster {
     /// * Fetching UTXOs from the Bitcoin node fails.
     /// * Filtering UTXOs encounters an issue.
     /// * Reading user input fails.
-    pub fn list_dust(&self, min_relay_fee: u64, address: &Option<String>) -> Result<(), Box<dyn Error>> {
-        let utxos = self.client.list_unspent(None,None,None,None,None).unwrap();
+    pub fn list_dust(&self, min_relay_fee: Option<u64>, address: &Option<String>) -> Result<(), Box<dyn Error>> {
+        let min_relay_fee = match min_relay_fee {
+            Some(fee) => fee,
+            None => query_min_relay_fee(&self.client)?,
+        };
+        let utxos = self.client.list_unspent(None,None,None,None,None)?;
         let dust_utxos = get_dust_utxos(&utxos, min_relay_fee, address)?;
         if dust_utxos.is_empty() {
             println!("No UTXOs found");
@@ -91,28 +136,114 @@ impl DustBuster {
     ///
     /// # Arguments
     ///
-    /// * `min_relay_fee` - The minimum relay fee rate (in satoshis per vByte) used to classify dust UTXOs.
-    /// * `address` - The address whose dust UTXOs should be selected. If empty, all dust UTXOs are considered.
-    /// * `utxo_count` - The maximum number of UTXOs to include in the PSBT.
+    /// * `selection` - Which dust UTXOs to select; see [`DustSelection`]. If its `destination`
+    ///   is set, the dust is consolidated into a single output at that address instead of
+    ///   the OP_RETURN output.
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// * No dust UTXOs are found.
+    /// * No dust UTXOs are found, or none are economically viable to spend at the current fee rate.
+    /// * An outpoint could not be checked against `ord_url` and `force` is `false`.
+    /// * `destination` is set and the consolidated output would be below its dust limit.
     /// * The PSBT creation fails.
     /// * Any client interaction results in an error.
+    pub fn create_psbt(&self, selection: DustSelection) -> Result<(), Box<dyn Error>> {
+        let (psbt, _) = self.build_dust_psbt(selection)?;
+        println!("{}", general_purpose::STANDARD.encode(psbt.serialize()));
+        Ok(())
+    }
+
+    /// Signs, finalizes, verifies, and broadcasts a PSBT spending dust UTXOs.
     ///
-    /// # Panics
+    /// This builds the same PSBT as [`DustBuster::create_psbt`], then hands it to the
+    /// wallet for signing via `walletprocesspsbt` and finalizes it via `finalizepsbt`.
+    /// Before broadcasting, every input's script is checked against its prevout with
+    /// libbitcoinconsensus so a malformed PSBT is caught locally rather than rejected
+    /// (or worse, silently wasted) by the node. Unless `dry_run` is set, the finalized
+    /// transaction is then broadcast with `sendrawtransaction` and the resulting txid is
+    /// printed; with `dry_run`, the finalized transaction hex is printed instead so it
+    /// can be inspected first.
     ///
-    /// This function panics if no dust UTXOs are found.
-    pub fn create_psbt(&self, min_relay_fee: u64, address: String, utxo_count: u64) -> Result<(), Box<dyn Error>> {
-        let utxos = self.client.list_unspent(None,None,None,None,None).unwrap();
-        let dust_utxos = get_dust_utxos(&utxos, min_relay_fee, &Some(address))?;
-        if dust_utxos.is_empty() {
-            panic!("No UTXOs found");
+    /// # Arguments
+    ///
+    /// * `selection` - Which dust UTXOs to select; see [`DustSelection`].
+    /// * `dry_run` - If `true`, stop after finalization instead of broadcasting.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * No dust UTXOs are found, or none are economically viable to spend at the current fee rate.
+    /// * An outpoint could not be checked against `ord_url` and `force` is `false`.
+    /// * `destination` is set and the consolidated output would be below its dust limit.
+    /// * The PSBT creation, signing, or finalization fails.
+    /// * Consensus verification fails for any input.
+    /// * The node rejects the broadcast.
+    pub fn sweep(&self, selection: DustSelection, dry_run: bool) -> Result<(), Box<dyn Error>> {
+        let (psbt, prevouts) = self.build_dust_psbt(selection)?;
+        let unsigned_psbt = general_purpose::STANDARD.encode(psbt.serialize());
+
+        let processed = self.client.wallet_process_psbt(&unsigned_psbt, Some(true), None, None)?;
+        let finalized = self.client.finalize_psbt(&processed.psbt, Some(true))?;
+        if !finalized.complete {
+            return Err("Failed to finalize PSBT: wallet could not fully sign it".into());
+        }
+        let tx_hex = finalized.hex.ok_or("Finalized PSBT did not contain a transaction")?;
+        let tx: Transaction = bitcoin::consensus::encode::deserialize(&tx_hex)?;
+
+        if let Err(failures) = verify_finalized_transaction(&tx, &prevouts) {
+            let details = failures.iter().map(|failure| failure.to_string()).collect::<Vec<_>>().join("; ");
+            return Err(format!("Consensus verification failed before broadcast: {}", details).into());
         }
-        let psbt = create_dust_psbt(&dust_utxos, utxo_count)?;
-        println!("{}", general_purpose::STANDARD.encode(&psbt.serialize()));
+
+        if dry_run {
+            println!("{}", tx_hex.to_lower_hex_string());
+            return Ok(());
+        }
+
+        let txid = self.client.send_raw_transaction(&tx_hex)?;
+        println!("Broadcast txid: {}", txid);
         Ok(())
     }
+
+    /// Selects dust UTXOs for `selection.address` and builds the unsigned PSBT spending them,
+    /// resolving `selection.min_relay_fee` from the node when not explicitly provided.
+    ///
+    /// Returns the PSBT along with the prevout UTXOs actually included, in input order,
+    /// for later consensus verification of the signed transaction.
+    fn build_dust_psbt(&self, selection: DustSelection) -> Result<(Psbt, Vec<ListUnspentResultEntry>), Box<dyn Error>> {
+        let DustSelection { min_relay_fee, address, utxo_count, ord_url, force, destination } = selection;
+        let min_relay_fee = match min_relay_fee {
+            Some(fee) => fee,
+            None => query_min_relay_fee(&self.client)?,
+        };
+        let utxos = self.client.list_unspent(None,None,None,None,None)?;
+        let mut dust_utxos = get_dust_utxos(&utxos, min_relay_fee, &Some(address))?;
+        if let Some(ord_url) = ord_url {
+            dust_utxos = filter_protected_utxos(dust_utxos, &ord_url, force)?;
+        }
+        if dust_utxos.is_empty() {
+            return Err("No dust UTXOs found".into());
+        }
+        let destination_script = match destination {
+            Some(addr) => {
+                let network = self.client.get_blockchain_info()?.chain;
+                Some(Address::from_str(&addr)?.require_network(network)?.script_pubkey())
+            }
+            None => None,
+        };
+        dust_utxos = select_economic_utxos(dust_utxos, min_relay_fee, destination_script.is_some());
+        if dust_utxos.is_empty() {
+            return Err("No dust UTXOs are economically viable to spend at the current fee rate".into());
+        }
+        let included: Vec<_> = dust_utxos.into_iter().take(utxo_count as usize).collect();
+
+        let total_value = included.iter().fold(Amount::ZERO, |acc, utxo| acc + utxo.amount);
+        let vsize = estimate_sweep_vbytes(included.iter().map(|utxo| &utxo.script_pub_key), destination_script.as_ref());
+        let total_fee = Amount::from_sat((vsize * min_relay_fee as f64).round() as u64);
+        println!("Selected {} UTXOs: total value {}, estimated spending fee {}", included.len(), total_value, total_fee);
+
+        let psbt = create_dust_psbt(&included, utxo_count, destination_script.as_ref(), min_relay_fee)?;
+        Ok((psbt, included))
+    }
 }
\ No newline at end of file
@@ -3,7 +3,7 @@ mod util;
 use crate::util::parse_proxy_auth;
 use clap::{Parser, Subcommand};
 use bitcoind::bitcoincore_rpc::{Auth, Client};
-use dustbuster::DustBuster;
+use dustbuster::{DustBuster, DustSelection};
 use crate::rpc::{RPCConfig, RPCError};
 
 #[derive(Parser)]
@@ -35,24 +35,60 @@ struct Cli {
 enum Commands {
     /// List dust UTXOs
     ListDust {
-        /// Min Relay Fee rate in sat/vB
-        #[arg(short, long, default_value_t = 1)]
-        min_relay_fee: u64,
+        /// Min Relay Fee rate in sat/vB. If omitted, the live minimum mempool fee is
+        /// fetched from the connected node via `getmempoolinfo`.
+        #[arg(short, long)]
+        min_relay_fee: Option<u64>,
         /// Bitcoin address to filter utxos by
         #[arg(short, long)]
         address: Option<String>,
     },
     /// Create an unsigned PSBT spending dust utxos to fees
     CreatePsbt {
-        /// Min Relay Fee rate in sat/vB
-        #[arg(short, long, default_value_t = 1)]
-        min_relay_fee: u64,
+        /// Min Relay Fee rate in sat/vB. If omitted, the live minimum mempool fee is
+        /// fetched from the connected node via `getmempoolinfo`.
+        #[arg(short, long)]
+        min_relay_fee: Option<u64>,
         /// Bitcoin address to filter utxos by
         #[arg(short, long)]
         address: String,
         /// Number of utxos to be included
         #[arg(short, long, default_value_t = 100)]
         utxo_count: u64,
+        /// Base URL of an ord server; UTXOs carrying inscriptions or runes are excluded
+        #[arg(long)]
+        ord_url: Option<String>,
+        /// Include outpoints that could not be checked against --ord-url instead of aborting
+        #[arg(long, default_value_t = false)]
+        force: bool,
+        /// Consolidate the dust into a single output at this address instead of burning it
+        #[arg(long)]
+        destination: Option<String>,
+    },
+    /// Sign, finalize, and broadcast a transaction spending dust utxos to fees
+    Sweep {
+        /// Min Relay Fee rate in sat/vB. If omitted, the live minimum mempool fee is
+        /// fetched from the connected node via `getmempoolinfo`.
+        #[arg(short, long)]
+        min_relay_fee: Option<u64>,
+        /// Bitcoin address to filter utxos by
+        #[arg(short, long)]
+        address: String,
+        /// Number of utxos to be included
+        #[arg(short, long, default_value_t = 100)]
+        utxo_count: u64,
+        /// Stop after finalizing the transaction and print its hex instead of broadcasting
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+        /// Base URL of an ord server; UTXOs carrying inscriptions or runes are excluded
+        #[arg(long)]
+        ord_url: Option<String>,
+        /// Include outpoints that could not be checked against --ord-url instead of aborting
+        #[arg(long, default_value_t = false)]
+        force: bool,
+        /// Consolidate the dust into a single output at this address instead of burning it
+        #[arg(long)]
+        destination: Option<String>,
     },
 }
 
@@ -69,12 +105,32 @@ fn main() -> Result<(), RPCError> {
     
     let dust_buster = DustBuster::new(rpc);
 
-    let _ = match &args.command {
+    let result = match &args.command {
         Commands::ListDust { min_relay_fee, address } => {
             dust_buster.list_dust(*min_relay_fee, address)
         },
-        Commands::CreatePsbt { min_relay_fee, address, utxo_count } => dust_buster.create_psbt(*min_relay_fee, address.to_string(), *utxo_count),
+        Commands::CreatePsbt { min_relay_fee, address, utxo_count, ord_url, force, destination } => dust_buster.create_psbt(DustSelection {
+            min_relay_fee: *min_relay_fee,
+            address: address.to_string(),
+            utxo_count: *utxo_count,
+            ord_url: ord_url.clone(),
+            force: *force,
+            destination: destination.clone(),
+        }),
+        Commands::Sweep { min_relay_fee, address, utxo_count, dry_run, ord_url, force, destination } => dust_buster.sweep(DustSelection {
+            min_relay_fee: *min_relay_fee,
+            address: address.to_string(),
+            utxo_count: *utxo_count,
+            ord_url: ord_url.clone(),
+            force: *force,
+            destination: destination.clone(),
+        }, *dry_run),
     };
+
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
     Ok(())
 }
 
@@ -0,0 +1,67 @@
+use std::fmt;
+use bitcoin::Txid;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// The subset of an ord server's `/output/<OUTPOINT>` response this crate cares about:
+/// whether the output carries any inscriptions or rune balances.
+#[derive(Debug, Deserialize)]
+pub struct OrdOutputInfo {
+    #[serde(default)]
+    pub inscriptions: Vec<Value>,
+    #[serde(default)]
+    pub runes: Value,
+}
+
+impl OrdOutputInfo {
+    /// Returns `true` if this output carries an inscription or a rune balance.
+    pub fn is_protected(&self) -> bool {
+        !self.inscriptions.is_empty() || match &self.runes {
+            Value::Object(map) => !map.is_empty(),
+            Value::Array(arr) => !arr.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum OrdError {
+    Request(Box<ureq::Error>),
+    Parse(std::io::Error),
+}
+
+impl fmt::Display for OrdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Request(err) => write!(f, "request to ord server failed: {}", err),
+            Self::Parse(err) => write!(f, "could not parse ord server response: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for OrdError {}
+
+impl From<ureq::Error> for OrdError {
+    fn from(value: ureq::Error) -> Self {
+        Self::Request(Box::new(value))
+    }
+}
+
+impl From<std::io::Error> for OrdError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Parse(value)
+    }
+}
+
+/// Queries an ord server for everything it knows about a single outpoint.
+///
+/// Issues `GET {ord_url}/output/{txid}:{vout}` with an `Accept: application/json` header,
+/// mirroring how a wallet's "identify" flow checks an outpoint before spending it.
+pub fn fetch_output_info(ord_url: &str, txid: &Txid, vout: u32) -> Result<OrdOutputInfo, OrdError> {
+    let url = format!("{}/output/{}:{}", ord_url.trim_end_matches('/'), txid, vout);
+    let info = ureq::get(&url)
+        .set("Accept", "application/json")
+        .call()?
+        .into_json()?;
+    Ok(info)
+}
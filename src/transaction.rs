@@ -1,8 +1,30 @@
 use std::collections::HashMap;
 use std::error::Error;
+use std::fmt;
 use bitcoin::{Amount, FeeRate, OutPoint, Psbt, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
 use bitcoin::transaction::Version;
 use bitcoind::bitcoincore_rpc::bitcoincore_rpc_json::ListUnspentResultEntry;
+use crate::ord::fetch_output_info;
+
+/// A single input's script verification failure, identified by its position in the transaction.
+///
+/// `bitcoin::consensus::validation` (and `Script::verify`, used in `verify_finalized_transaction`
+/// below) only exists when the `bitcoin` crate is built with its `bitcoinconsensus` feature
+/// enabled; the manifest needs `bitcoin = { version = "0.32", features = ["bitcoinconsensus"] }`
+/// for this module to compile.
+#[derive(Debug)]
+pub struct InputVerificationError {
+    pub input_index: usize,
+    pub error: bitcoin::consensus::validation::BitcoinconsensusError,
+}
+
+impl fmt::Display for InputVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "input {} failed consensus verification: {:?}", self.input_index, self.error)
+    }
+}
+
+impl std::error::Error for InputVerificationError {}
 
 /// Filters and returns dust UTXOs from a given list of UTXOs.
 ///
@@ -57,26 +79,73 @@ pub fn create_op_return_output_script() -> Vec<u8> {
     script_pubkey
 }
 
-/// Creates a PSBT (Partially Signed Bitcoin Transaction) that spends dust UTXOs to an OP_RETURN output.
+/// The estimated fixed overhead, in vbytes, of a version 2 segwit transaction's
+/// version, locktime, segwit marker/flag, and input/output counts.
+const BASE_TX_VBYTES: f64 = 10.5;
+
+/// Estimates the virtual size, in vbytes, of spending a single input of the given script type
+/// with a single signature. Conservative per-script-type approximations; actual size can vary
+/// slightly with signature length.
+pub(crate) fn estimate_input_vbytes(script_pubkey: &ScriptBuf) -> f64 {
+    if script_pubkey.is_p2wpkh() {
+        68.0
+    } else if script_pubkey.is_p2tr() {
+        57.5
+    } else {
+        148.0 // P2PKH and other legacy types
+    }
+}
+
+/// Estimates the virtual size, in vbytes, of a single output paying the given script type.
+fn estimate_output_vbytes(script_pubkey: &ScriptBuf) -> f64 {
+    if script_pubkey.is_p2wpkh() {
+        31.0
+    } else if script_pubkey.is_p2tr() {
+        43.0
+    } else {
+        34.0 // P2PKH and other legacy types
+    }
+}
+
+/// Estimates the total virtual size, in vbytes, of a transaction spending `input_scripts` to a
+/// single output, matching the shape `create_dust_psbt` builds: `BASE_TX_VBYTES` plus one input
+/// per script plus one output, either at `destination` or an OP_RETURN when `destination` is `None`.
+pub(crate) fn estimate_sweep_vbytes<'a>(input_scripts: impl Iterator<Item = &'a ScriptBuf>, destination: Option<&ScriptBuf>) -> f64 {
+    let input_vbytes: f64 = input_scripts.map(estimate_input_vbytes).sum();
+    let output_vbytes = match destination {
+        Some(destination_script) => estimate_output_vbytes(destination_script),
+        None => estimate_output_vbytes(&ScriptBuf::from_bytes(create_op_return_output_script())),
+    };
+    BASE_TX_VBYTES + input_vbytes + output_vbytes
+}
+
+/// Creates a PSBT (Partially Signed Bitcoin Transaction) that spends dust UTXOs.
 ///
-/// This function takes a list of UTXOs and constructs an unsigned transaction that spends them
-/// to an OP_RETURN output, effectively removing them from circulation.
+/// Without a `destination`, the UTXOs are spent to an empty OP_RETURN output, effectively
+/// removing them from circulation. With a `destination`, they are consolidated into a single
+/// output at that script instead: the output value is the total input amount minus an
+/// estimated fee (`vsize * fee_rate`), and the build is refused if that remainder would be
+/// below the destination's own dust limit.
 ///
 /// # Arguments
 ///
 /// * `utxos` - A reference to a vector of UTXOs to be included in the transaction.
 /// * `utxo_count` - The number of UTXOs to include in the transaction.
-///
-/// # Returns
-///
-/// Returns a `Result` containing the created PSBT or an error.
+/// * `destination` - An optional destination script to consolidate the dust into, instead of
+///   burning it to an OP_RETURN output.
+/// * `fee_rate` - The fee rate (in sat/vB) used to compute the consolidation fee and the
+///   destination's dust limit. Unused when `destination` is `None`.
 ///
 /// # Errors
 ///
 /// * If the PSBT creation fails due to an issue with transaction inputs or outputs.
+/// * If `destination` is set and the total dust value cannot cover the consolidation fee, or
+///   the remainder would be below the destination's dust limit.
 ///
-pub fn create_dust_psbt(utxos: &Vec<ListUnspentResultEntry>, utxo_count: u64) -> Result<Psbt, bitcoin::psbt::Error> {
-    let inputs: Vec<TxIn> = utxos.iter().take(utxo_count as usize).map(|utxo| {
+pub fn create_dust_psbt(utxos: &[ListUnspentResultEntry], utxo_count: u64, destination: Option<&ScriptBuf>, fee_rate: u64) -> Result<Psbt, Box<dyn Error>> {
+    let selected: Vec<_> = utxos.iter().take(utxo_count as usize).collect();
+
+    let inputs: Vec<TxIn> = selected.iter().map(|utxo| {
         TxIn {
             previous_output: OutPoint { txid: utxo.txid, vout: utxo.vout },
             script_sig: ScriptBuf::new(),
@@ -85,9 +154,32 @@ pub fn create_dust_psbt(utxos: &Vec<ListUnspentResultEntry>, utxo_count: u64) ->
         }
     }).collect();
 
-    let tx_out = TxOut {
-        value: Amount::from_sat(0),
-        script_pubkey: ScriptBuf::from_bytes(create_op_return_output_script()),
+    let tx_out = match destination {
+        Some(destination_script) => {
+            let total_input = selected.iter().fold(Amount::ZERO, |acc, utxo| acc + utxo.amount);
+            let vsize = estimate_sweep_vbytes(selected.iter().map(|utxo| &utxo.script_pub_key), Some(destination_script));
+            let fee = Amount::from_sat((vsize * fee_rate as f64).round() as u64);
+
+            let remainder = total_input.checked_sub(fee)
+                .ok_or("Total dust value is too small to cover the consolidation fee")?;
+
+            let dust_limit = destination_script.minimal_non_dust_custom(FeeRate::from_sat_per_vb_u32(fee_rate as u32));
+            if remainder < dust_limit {
+                return Err(format!(
+                    "Consolidated output of {} would be below the destination's dust limit of {}",
+                    remainder, dust_limit
+                ).into());
+            }
+
+            TxOut {
+                value: remainder,
+                script_pubkey: destination_script.clone(),
+            }
+        }
+        None => TxOut {
+            value: Amount::from_sat(0),
+            script_pubkey: ScriptBuf::from_bytes(create_op_return_output_script()),
+        },
     };
 
     let outputs: Vec<TxOut> = vec![tx_out];
@@ -99,7 +191,111 @@ pub fn create_dust_psbt(utxos: &Vec<ListUnspentResultEntry>, utxo_count: u64) ->
         output: outputs,
     };
 
-    Psbt::from_unsigned_tx(unsigned_tx)
+    Ok(Psbt::from_unsigned_tx(unsigned_tx)?)
+}
+
+/// Verifies that every input of a finalized transaction satisfies the spending conditions
+/// of its prevout, using libbitcoinconsensus with `Script::verify`'s default flag set
+/// (`VERIFY_ALL_PRE_TAPROOT`): the pre-taproot consensus rules, without the additional
+/// policy rules a node applies at relay time. Taproot (P2TR) spends are not checked
+/// under these flags.
+///
+/// `prevouts` must be positionally aligned with `tx`'s inputs, i.e. `prevouts[i]` is the
+/// UTXO spent by `tx.input[i]` (the same ordering `create_dust_psbt` used to build `tx`).
+///
+/// # Arguments
+///
+/// * `tx` - The finalized, fully-signed transaction to verify.
+/// * `prevouts` - The UTXOs spent by `tx`, in input order.
+///
+/// # Errors
+///
+/// Returns one `InputVerificationError` per input that fails consensus verification.
+///
+pub fn verify_finalized_transaction(tx: &Transaction, prevouts: &[ListUnspentResultEntry]) -> Result<(), Vec<InputVerificationError>> {
+    let tx_bytes = bitcoin::consensus::encode::serialize(tx);
+
+    let failures: Vec<_> = prevouts.iter().enumerate()
+        .filter_map(|(input_index, prevout)| {
+            prevout.script_pub_key
+                .verify(input_index, prevout.amount, &tx_bytes)
+                .err()
+                .map(|error| InputVerificationError { input_index, error })
+        })
+        .collect();
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures)
+    }
+}
+
+/// Drops any UTXO in `utxos` that an ord server reports as carrying inscriptions or runes.
+///
+/// Burning a UTXO that actually holds an ordinal/inscription/rune would destroy that asset,
+/// so every candidate outpoint is checked against `ord_url` before it is allowed into a PSBT.
+///
+/// # Arguments
+///
+/// * `utxos` - The dust UTXOs being considered for inclusion in the sweep.
+/// * `ord_url` - Base URL of the ord server used to look up each outpoint.
+/// * `force` - If `true`, an outpoint that could not be checked is kept instead of
+///   blocking the whole pass.
+///
+/// # Errors
+///
+/// Returns an error if any outpoint could not be checked and `force` is `false`.
+///
+pub fn filter_protected_utxos(utxos: Vec<ListUnspentResultEntry>, ord_url: &str, force: bool) -> Result<Vec<ListUnspentResultEntry>, Box<dyn Error>> {
+    let mut kept = Vec::with_capacity(utxos.len());
+    for utxo in utxos {
+        match fetch_output_info(ord_url, &utxo.txid, utxo.vout) {
+            Ok(info) if info.is_protected() => {
+                println!("Skipping {}:{} - carries an inscription or rune", utxo.txid, utxo.vout);
+            }
+            Ok(_) => kept.push(utxo),
+            Err(err) if force => {
+                println!("Warning: could not check {}:{} ({}), including it due to --force", utxo.txid, utxo.vout, err);
+                kept.push(utxo);
+            }
+            Err(err) => {
+                return Err(format!("Could not verify {}:{} is free of inscriptions/runes: {}", utxo.txid, utxo.vout, err).into());
+            }
+        }
+    }
+    Ok(kept)
+}
+
+/// Filters and orders dust UTXOs by economic viability at the given fee rate.
+///
+/// In consolidation mode (`consolidating` is `true`), a UTXO is only worth spending if its
+/// amount exceeds the marginal cost of including it (`estimate_input_vbytes * fee_rate`);
+/// unprofitable UTXOs are dropped so the tool never produces a transaction that loses money.
+/// In burn mode (`consolidating` is `false`), nothing is dropped, but UTXOs are sorted
+/// ascending by amount so the most hopeless dust is prioritized for inclusion first.
+///
+/// # Arguments
+///
+/// * `utxos` - The dust UTXOs considered for inclusion.
+/// * `fee_rate` - The fee rate (in sat/vB) used to estimate each UTXO's marginal spending cost.
+/// * `consolidating` - Whether the resulting UTXOs will be consolidated to an address (`true`)
+///   or burned to an OP_RETURN output (`false`).
+///
+/// # Returns
+///
+/// Returns the surviving UTXOs, ordered for inclusion.
+///
+pub fn select_economic_utxos(mut utxos: Vec<ListUnspentResultEntry>, fee_rate: u64, consolidating: bool) -> Vec<ListUnspentResultEntry> {
+    if consolidating {
+        utxos.retain(|utxo| {
+            let marginal_cost = Amount::from_sat((estimate_input_vbytes(&utxo.script_pub_key) * fee_rate as f64).round() as u64);
+            utxo.amount > marginal_cost
+        });
+    } else {
+        utxos.sort_by_key(|utxo| utxo.amount);
+    }
+    utxos
 }
 
 /// Groups UTXOs by their associated Bitcoin address.